@@ -10,11 +10,11 @@ use anyhow::{bail, Context};
 use bytemuck::bytes_of;
 use futures::TryStreamExt;
 use futures_core::Future;
-use libsql_replication::frame::FrameMut;
+use libsql_replication::frame::{Frame, FrameMut};
 use libsql_replication::snapshot::{SnapshotFile, SnapshotFileHeader};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::mpsc;
 use tokio_stream::{Stream, StreamExt};
 use uuid::Uuid;
@@ -30,9 +30,182 @@ use super::FrameNo;
 const SNAPHOT_SPACE_AMPLIFICATION_FACTOR: u64 = 2;
 /// The maximum amount of snapshot allowed before a compaction is required
 const MAX_SNAPSHOT_NUMBER: usize = 32;
+/// Default zstd compression level used for archive export.
+const DEFAULT_SNAPSHOT_COMPRESSION_LEVEL: i32 = 3;
+
+/// `_pad` bit set when a 32-byte blake3 content hash is appended as a footer after the frame
+/// region.
+const CONTENT_HASH_FLAG: u64 = 0x100;
+/// Size in bytes of the content hash footer (blake3 digest).
+const CONTENT_HASH_LEN: usize = 32;
+/// `_pad` bit set when a sorted page-offset index is written between the frame region and the
+/// content hash footer. The index length is stored as an 8-byte little-endian value immediately
+/// before the content hash so the footer can be located without a header field.
+const PAGE_INDEX_FLAG: u64 = 0x200;
+/// Size of a single page-offset index entry: `page_no: u32` + `offset: u64`.
+const PAGE_INDEX_ENTRY_LEN: usize = size_of::<u32>() + size_of::<u64>();
+
+/// Error raised when a snapshot fails integrity verification.
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotIntegrityError {
+    #[error("snapshot `{path}` failed content hash verification (expected {expected}, got {actual})")]
+    ContentHashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("snapshot `{path}` is truncated: expected at least {expected} bytes, got {actual}")]
+    Truncated {
+        path: String,
+        expected: usize,
+        actual: u64,
+    },
+}
 
-/// returns (db_id, start_frame_no, end_frame_no) for the given snapshot name
-fn parse_snapshot_name(name: &str) -> Option<(Uuid, u64, u64)> {
+/// Re-hash the frame region and page-offset index of a snapshot and compare it against the footer
+/// digest, if present.
+///
+/// Snapshots written before content hashing (no [`CONTENT_HASH_FLAG`]) verify trivially so that
+/// old files keep opening. Returns a typed [`SnapshotIntegrityError`] on mismatch or truncation.
+async fn verify_snapshot_integrity(path: &Path) -> anyhow::Result<()> {
+    let header_len = size_of::<SnapshotFileHeader>();
+    let mut file = tokio::fs::File::open(path).await?;
+    let file_len = file.metadata().await?.len();
+
+    let mut header_bytes = vec![0u8; header_len];
+    file.read_exact(&mut header_bytes).await?;
+    let header: SnapshotFileHeader = bytemuck::pod_read_unaligned(&header_bytes);
+    if header._pad & CONTENT_HASH_FLAG == 0 {
+        return Ok(());
+    }
+
+    if file_len < (header_len + CONTENT_HASH_LEN) as u64 {
+        return Err(SnapshotIntegrityError::Truncated {
+            path: path.display().to_string(),
+            expected: header_len + CONTENT_HASH_LEN,
+            actual: file_len,
+        }
+        .into());
+    }
+
+    // Hash everything between the header and the footer (frames, then the page-offset index and
+    // its length) in bounded chunks instead of slurping the whole (potentially multi-GB) snapshot
+    // into memory; the digest footer is the trailing `CONTENT_HASH_LEN` bytes.
+    let frames_end = file_len - CONTENT_HASH_LEN as u64;
+    let mut hasher = blake3::Hasher::new();
+    let mut remaining = frames_end - header_len as u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    while remaining > 0 {
+        let want = remaining.min(buf.len() as u64) as usize;
+        file.read_exact(&mut buf[..want]).await?;
+        hasher.update(&buf[..want]);
+        remaining -= want as u64;
+    }
+    let actual = hasher.finalize();
+
+    let mut expected = [0u8; CONTENT_HASH_LEN];
+    file.read_exact(&mut expected).await?;
+    if actual.as_bytes() != &expected {
+        return Err(SnapshotIntegrityError::ContentHashMismatch {
+            path: path.display().to_string(),
+            expected: hex::encode(expected),
+            actual: actual.to_hex().to_string(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Read a single page's frame directly from a snapshot by binary-searching its page-offset index,
+/// without streaming the whole file. Returns `None` if the snapshot has no index footer or does
+/// not contain the page.
+///
+/// Each `page_no` appears exactly once in a snapshot, so the index is a total map from page to the
+/// byte offset of its frame record; this makes point reads against large snapshots cheap while the
+/// sequential stream API is left untouched.
+async fn frame_for_page(path: &Path, page_no: u32) -> anyhow::Result<Option<Frame>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let file_len = file.metadata().await?.len();
+
+    let mut header_bytes = [0u8; size_of::<SnapshotFileHeader>()];
+    file.read_exact(&mut header_bytes).await?;
+    let header: SnapshotFileHeader = bytemuck::pod_read_unaligned(&header_bytes);
+    if header._pad & PAGE_INDEX_FLAG == 0 {
+        return Ok(None);
+    }
+
+    // Locate the `index_len` word, skipping the content hash footer if present.
+    let hash_len = if header._pad & CONTENT_HASH_FLAG != 0 {
+        CONTENT_HASH_LEN as u64
+    } else {
+        0
+    };
+    let index_len_pos = file_len - hash_len - size_of::<u64>() as u64;
+    file.seek(SeekFrom::Start(index_len_pos)).await?;
+    let mut len_buf = [0u8; size_of::<u64>()];
+    file.read_exact(&mut len_buf).await?;
+    let index_len = u64::from_le_bytes(len_buf);
+    let index_start = index_len_pos - index_len;
+
+    // Binary search the sorted (page_no, offset) entries.
+    let entry_count = (index_len as usize) / PAGE_INDEX_ENTRY_LEN;
+    let (mut lo, mut hi) = (0usize, entry_count);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        file.seek(SeekFrom::Start(
+            index_start + (mid * PAGE_INDEX_ENTRY_LEN) as u64,
+        ))
+        .await?;
+        let mut entry = [0u8; PAGE_INDEX_ENTRY_LEN];
+        file.read_exact(&mut entry).await?;
+        let mid_page = u32::from_le_bytes(entry[..4].try_into().unwrap());
+        let mid_offset = u64::from_le_bytes(entry[4..].try_into().unwrap());
+        match mid_page.cmp(&page_no) {
+            std::cmp::Ordering::Equal => {
+                return Ok(Some(read_frame_at(&mut file, mid_offset).await?));
+            }
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+        }
+    }
+
+    Ok(None)
+}
+
+/// Read the fixed-size frame record stored at `offset`.
+async fn read_frame_at(file: &mut tokio::fs::File, offset: u64) -> anyhow::Result<Frame> {
+    file.seek(SeekFrom::Start(offset)).await?;
+    let mut buf = vec![0u8; LogFile::FRAME_SIZE];
+    file.read_exact(&mut buf).await?;
+    Frame::try_from(buf.as_slice()).map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Move a snapshot that failed verification out of the active set so it is not merged or served.
+async fn quarantine_snapshot(path: &Path) -> anyhow::Result<()> {
+    let quarantined = path.with_extension("snap.corrupt");
+    tracing::warn!("quarantining corrupt snapshot {path:?} -> {quarantined:?}");
+    tokio::fs::rename(path, &quarantined).await?;
+    Ok(())
+}
+
+/// Parsed metadata recovered from a snapshot file name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotName {
+    pub db_id: Uuid,
+    pub start_frame_no: u64,
+    pub end_frame_no: u64,
+}
+
+impl SnapshotName {
+    /// Render the canonical `{uuid}-{start}-{end}.snap` file name for this snapshot.
+    fn to_file_name(self) -> String {
+        format!("{}-{}-{}.snap", self.db_id, self.start_frame_no, self.end_frame_no)
+    }
+}
+
+/// Parse a snapshot file name into its [`SnapshotName`].
+fn parse_snapshot_meta(name: &str) -> Option<SnapshotName> {
     static SNAPSHOT_FILE_MATCHER: Lazy<Regex> = Lazy::new(|| {
         Regex::new(
             r"(?x)
@@ -45,18 +218,46 @@ fn parse_snapshot_name(name: &str) -> Option<(Uuid, u64, u64)> {
         )
         .unwrap()
     });
-    let Some(captures) = SNAPSHOT_FILE_MATCHER.captures(name) else {
-        return None;
-    };
-    let db_id = captures.get(1).unwrap();
-    let start_index: u64 = captures.get(2).unwrap().as_str().parse().unwrap();
-    let end_index: u64 = captures.get(3).unwrap().as_str().parse().unwrap();
-
-    Some((
-        Uuid::from_str(db_id.as_str()).unwrap(),
-        start_index,
-        end_index,
-    ))
+
+    let captures = SNAPSHOT_FILE_MATCHER.captures(name)?;
+    let db_id = Uuid::from_str(captures.get(1).unwrap().as_str()).ok()?;
+    let start_frame_no = captures.get(2).unwrap().as_str().parse().ok()?;
+    let end_frame_no = captures.get(3).unwrap().as_str().parse().ok()?;
+    Some(SnapshotName {
+        db_id,
+        start_frame_no,
+        end_frame_no,
+    })
+}
+
+/// returns (db_id, start_frame_no, end_frame_no) for the given snapshot name
+fn parse_snapshot_name(name: &str) -> Option<(Uuid, u64, u64)> {
+    let meta = parse_snapshot_meta(name)?;
+    Some((meta.db_id, meta.start_frame_no, meta.end_frame_no))
+}
+
+/// Suffix of in-progress snapshot files, renamed into place once complete.
+const TMP_SNAPSHOT_SUFFIX: &str = ".tmp";
+
+/// Delete any leftover `.tmp` snapshots from an interrupted write. A file under its final name is
+/// always complete (write-to-temp-then-rename), so leftover `.tmp` files are always partials.
+async fn remove_orphaned_tmp(snapshot_dir: &Path) -> anyhow::Result<()> {
+    if !snapshot_dir.exists() {
+        return Ok(());
+    }
+    let mut entries = tokio::fs::read_dir(snapshot_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(TMP_SNAPSHOT_SUFFIX))
+        {
+            tracing::warn!("removing leftover partial snapshot {path:?}");
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+    Ok(())
 }
 
 fn snapshot_list(db_path: &Path) -> impl Stream<Item = anyhow::Result<String>> + '_ {
@@ -67,6 +268,14 @@ fn snapshot_list(db_path: &Path) -> impl Stream<Item = anyhow::Result<String>> +
             let Some(name) = path.file_name() else {
                 continue;
             };
+            // ignore in-progress `.tmp` files: only fully renamed snapshots are visible to readers.
+            if name.to_str().is_some_and(|n| n.ends_with(TMP_SNAPSHOT_SUFFIX)) {
+                continue;
+            }
+            // ignore quarantined `.corrupt` files: they are out of the active set.
+            if name.to_str().is_some_and(|n| n.ends_with(".corrupt")) {
+                continue;
+            }
             let Some(name_str) = name.to_str() else {
                 continue;
             };
@@ -92,6 +301,9 @@ pub async fn find_snapshot_file(
         if (start_frame_no..=end_frame_no).contains(&frame_no) {
             let snapshot_path = snapshot_dir_path.join(&name);
             tracing::debug!("found snapshot for frame {frame_no} at {snapshot_path:?}");
+            // The content hash is not checked here: the file is opened directly, without
+            // re-hashing a multi-GB snapshot on every request. Hash verification (and quarantine of
+            // failures) happens only in the merger, at startup and before a merge.
             let snapshot_file = SnapshotFile::open(&snapshot_path).await?;
             return Ok(Some(snapshot_file));
         }
@@ -109,38 +321,33 @@ pub type SnapshotCallback = Box<dyn Fn(&Path) -> anyhow::Result<()> + Send + Syn
 pub type NamespacedSnapshotCallback =
     Arc<dyn Fn(&Path, &NamespaceName) -> anyhow::Result<()> + Send + Sync>;
 
-async fn compact(
-    db_path: &Path,
-    to_compact_file: LogFile,
-    log_id: Uuid,
+/// Commit a freshly produced snapshot: fire the callback, register it with the merger, and drop
+/// the source log file. This must run in frame-number order, so it is driven by the ordering
+/// buffer in [`LogCompactor::new`] rather than by the parallel workers.
+async fn commit_snapshot(
+    snapshot: (String, u64, u32),
     merger: &mut SnapshotMerger,
     callback: &SnapshotCallback,
     snapshot_dir_path: &Path,
     to_compact_path: &Path,
 ) -> anyhow::Result<()> {
-    match perform_compaction(&db_path, to_compact_file, log_id).await {
-        Ok((snapshot_name, snapshot_frame_count, size_after)) => {
-            tracing::info!("snapshot `{snapshot_name}` successfully created");
+    let (snapshot_name, snapshot_frame_count, size_after) = snapshot;
+    tracing::info!("snapshot `{snapshot_name}` successfully created");
 
-            let snapshot_file = snapshot_dir_path.join(&snapshot_name);
-            if let Err(e) = (*callback)(&snapshot_file) {
-                bail!("failed to call snapshot callback: {e}");
-            }
+    let snapshot_file = snapshot_dir_path.join(&snapshot_name);
+    if let Err(e) = (*callback)(&snapshot_file) {
+        bail!("failed to call snapshot callback: {e}");
+    }
 
-            if let Err(e) = merger
-                .register_snapshot(snapshot_name, snapshot_frame_count, size_after)
-                .await
-            {
-                bail!("failed to register snapshot with snapshot merger: {e}");
-            }
+    if let Err(e) = merger
+        .register_snapshot(snapshot_name, snapshot_frame_count, size_after)
+        .await
+    {
+        bail!("failed to register snapshot with snapshot merger: {e}");
+    }
 
-            if let Err(e) = std::fs::remove_file(&to_compact_path) {
-                bail!("failed to remove old log file `{to_compact_path:?}`: {e}",);
-            }
-        }
-        Err(e) => {
-            bail!("fatal error creating snapshot: {e}");
-        }
+    if let Err(e) = std::fs::remove_file(to_compact_path) {
+        bail!("failed to remove old log file `{to_compact_path:?}`: {e}",);
     }
 
     Ok(())
@@ -179,53 +386,100 @@ fn pending_snapshots_list(compact_queue_dir: &Path) -> anyhow::Result<Vec<(LogFi
 }
 
 impl LogCompactor {
+    // There is deliberately no compressed-snapshot variant of this constructor: the compactor can
+    // only emit a frame encoding the external `SnapshotFile` reader can consume, and that reader
+    // decodes nothing (see `SnapshotBuilder::append_frames`). Snapshots are therefore uncompressed;
+    // the only zstd use in this module is the self-contained archive export and import.
     pub fn new(db_path: &Path, log_id: Uuid, callback: SnapshotCallback) -> anyhow::Result<Self> {
         // a directory containing logs that need compaction
         let compact_queue_dir = db_path.join("to_compact");
         std::fs::create_dir_all(&compact_queue_dir)?;
         let (sender, mut receiver) = mpsc::channel::<(LogFile, PathBuf)>(8);
         let mut merger = SnapshotMerger::new(db_path, log_id)?;
-        let snapshot_dir_path = snapshot_dir_path(&db_path);
+        let snapshot_dir = snapshot_dir_path(db_path);
 
         let db_path = db_path.to_path_buf();
         // We gather pending snapshots here, so new snapshots don't interfere.
         let pending = pending_snapshots_list(&compact_queue_dir)?;
+        // Bound the number of logfiles compacted concurrently to available parallelism so the
+        // CPU-bound frame copying (plus the hash work) of a large backlog doesn't serialize,
+        // while keeping memory use in check.
+        let parallelism = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(parallelism));
+
+        // Completed snapshots flow back to the committer keyed by a monotonic sequence number, so
+        // that even though the heavy work finishes out of order they are registered with the
+        // merger in submission (frame-number) order.
+        let (done_tx, mut done_rx) =
+            mpsc::channel::<(u64, anyhow::Result<(String, u64, u32)>, PathBuf)>(parallelism);
+
+        // Committer: drains completed snapshots in sequence order.
+        tokio::task::spawn(async move {
+            let mut buffer: std::collections::BTreeMap<
+                u64,
+                (anyhow::Result<(String, u64, u32)>, PathBuf),
+            > = std::collections::BTreeMap::new();
+            let mut next_seq = 0u64;
+            while let Some((seq, result, to_compact_path)) = done_rx.recv().await {
+                buffer.insert(seq, (result, to_compact_path));
+                while let Some((result, to_compact_path)) = buffer.remove(&next_seq) {
+                    next_seq += 1;
+                    let snapshot = match result {
+                        Ok(snapshot) => snapshot,
+                        Err(e) => {
+                            tracing::error!("fatal error creating snapshot: {e}");
+                            return;
+                        }
+                    };
+                    if let Err(e) = commit_snapshot(
+                        snapshot,
+                        &mut merger,
+                        &callback,
+                        &snapshot_dir,
+                        &to_compact_path,
+                    )
+                    .await
+                    {
+                        tracing::error!("fatal compactor error: {e}");
+                        return;
+                    }
+                }
+            }
+        });
+
+        // Dispatcher: first the pending logs, then whatever arrives on the channel, in order. Each
+        // logfile is handed to a worker guarded by the semaphore.
         // FIXME(marin): we somehow need to make this code more robust. How to deal with a
         // compaction error?
         tokio::task::spawn(async move {
-            // process pending snapshots if any.
+            // remove any half-written snapshot left by a crash before producing new ones.
+            if let Err(e) = remove_orphaned_tmp(&snapshot_dir_path(&db_path)).await {
+                tracing::error!("failed to clean up leftover partial snapshots: {e}");
+                return;
+            }
+
+            let mut seq = 0u64;
+            let mut dispatch = |to_compact_file: LogFile, to_compact_path: PathBuf| {
+                let this_seq = seq;
+                seq += 1;
+                let semaphore = semaphore.clone();
+                let done_tx = done_tx.clone();
+                let db_path = db_path.clone();
+                tokio::task::spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    let result = perform_compaction(&db_path, to_compact_file, log_id).await;
+                    let _ = done_tx.send((this_seq, result, to_compact_path)).await;
+                });
+            };
+
             for (to_compact_file, to_compact_path) in pending {
-                if let Err(e) = compact(
-                    &db_path,
-                    to_compact_file,
-                    log_id,
-                    &mut merger,
-                    &callback,
-                    &snapshot_dir_path,
-                    &to_compact_path,
-                )
-                .await
-                {
-                    tracing::error!("fatal error while compacting pending logs: {e}");
-                    return;
-                }
+                dispatch(to_compact_file, to_compact_path);
             }
 
             while let Some((to_compact_file, to_compact_path)) = receiver.recv().await {
-                if let Err(e) = compact(
-                    &db_path,
-                    to_compact_file,
-                    log_id,
-                    &mut merger,
-                    &callback,
-                    &snapshot_dir_path,
-                    &to_compact_path,
-                )
-                .await
-                {
-                    tracing::error!("fatal compactor error: {e}");
-                    break;
-                }
+                dispatch(to_compact_file, to_compact_path);
             }
         });
 
@@ -243,6 +497,219 @@ impl LogCompactor {
     }
 }
 
+/// Compression applied to an exported snapshot archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// A member of an exported archive, mirroring a single `.snap` file.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub start_frame_no: u64,
+    pub end_frame_no: u64,
+    pub size: u64,
+    /// Hex-encoded content hash, when the snapshot carries one.
+    pub content_hash: Option<String>,
+}
+
+/// Manifest embedded as `manifest.json` at the head of an exported archive.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveManifest {
+    pub log_id: Uuid,
+    pub members: Vec<ArchiveMember>,
+}
+
+const ARCHIVE_MANIFEST_NAME: &str = "manifest.json";
+
+/// Read the stored content hash from a snapshot footer, if present.
+async fn snapshot_content_hash(path: &Path) -> anyhow::Result<Option<String>> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(snapshot_content_hash_bytes(&bytes))
+}
+
+/// Extract the hex-encoded footer content hash from an in-memory snapshot, if it carries one.
+fn snapshot_content_hash_bytes(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < size_of::<SnapshotFileHeader>() {
+        return None;
+    }
+    let header: SnapshotFileHeader =
+        bytemuck::pod_read_unaligned(&bytes[..size_of::<SnapshotFileHeader>()]);
+    if header._pad & CONTENT_HASH_FLAG == 0 || bytes.len() < CONTENT_HASH_LEN {
+        return None;
+    }
+    Some(hex::encode(&bytes[bytes.len() - CONTENT_HASH_LEN..]))
+}
+
+/// Bundling and restoration of a namespace's snapshot set as a single portable archive.
+///
+/// This gives operators a reproducible backup/restore and node-bootstrap primitive that
+/// round-trips through [`find_snapshot_file`] without manual file shuffling.
+pub struct SnapshotArchive;
+
+impl SnapshotArchive {
+    /// Bundle the current snapshot set for `log_id` into a single tar archive at `out`, prefixed
+    /// with a manifest describing each member's frame range, size, and content hash.
+    pub async fn export(
+        db_path: &Path,
+        log_id: Uuid,
+        out: &Path,
+        compression: ArchiveCompression,
+    ) -> anyhow::Result<()> {
+        let snapshot_dir_path = snapshot_dir_path(db_path);
+        let mut members = Vec::new();
+        let mut paths = Vec::new();
+
+        let names = snapshot_list(db_path);
+        tokio::pin!(names);
+        while let Some(name) = names.next().await.transpose()? {
+            let Some(meta) = parse_snapshot_meta(&name) else {
+                continue;
+            };
+            if meta.db_id.as_u128() != log_id.as_u128() {
+                continue;
+            }
+            let path = snapshot_dir_path.join(&name);
+            let size = tokio::fs::metadata(&path).await?.len();
+            members.push(ArchiveMember {
+                name: name.clone(),
+                start_frame_no: meta.start_frame_no,
+                end_frame_no: meta.end_frame_no,
+                size,
+                content_hash: snapshot_content_hash(&path).await?,
+            });
+            paths.push(path);
+        }
+
+        let manifest = ArchiveManifest { log_id, members };
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+        let out = out.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let file = std::fs::File::create(&out)?;
+            let writer: Box<dyn std::io::Write> = match compression {
+                ArchiveCompression::None => Box::new(file),
+                ArchiveCompression::Gzip => Box::new(flate2::write::GzEncoder::new(
+                    file,
+                    flate2::Compression::default(),
+                )),
+                ArchiveCompression::Zstd => Box::new(
+                    zstd::stream::write::Encoder::new(file, DEFAULT_SNAPSHOT_COMPRESSION_LEVEL)?
+                        .auto_finish(),
+                ),
+            };
+            let mut builder = tar::Builder::new(writer);
+
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest_bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, ARCHIVE_MANIFEST_NAME, manifest_bytes.as_slice())?;
+
+            for (path, member) in paths.iter().zip(manifest.members.iter()) {
+                let mut f = std::fs::File::open(path)?;
+                builder.append_file(&member.name, &mut f)?;
+            }
+
+            builder.into_inner()?.flush()?;
+            Ok(())
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// Unpack an archive into `snapshot_dir_path`, validating the embedded manifest against the
+    /// member file names and rejecting members whose frame range overlaps an existing snapshot.
+    pub async fn import(db_path: &Path, archive: &Path) -> anyhow::Result<()> {
+        let snapshot_dir_path = snapshot_dir_path(db_path);
+        std::fs::create_dir_all(&snapshot_dir_path)?;
+
+        // Existing ranges, used to reject overlapping members.
+        let mut existing = Vec::new();
+        let names = snapshot_list(db_path);
+        tokio::pin!(names);
+        while let Some(name) = names.next().await.transpose()? {
+            if let Some(meta) = parse_snapshot_meta(&name) {
+                existing.push((meta.start_frame_no, meta.end_frame_no));
+            }
+        }
+
+        let archive = archive.to_path_buf();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let file = std::fs::File::open(&archive)?;
+            let reader: Box<dyn std::io::Read> = match archive.extension().and_then(|e| e.to_str()) {
+                Some("gz") => Box::new(flate2::read::GzDecoder::new(file)),
+                Some("zst") => Box::new(zstd::stream::read::Decoder::new(file)?),
+                _ => Box::new(file),
+            };
+            let mut tar = tar::Archive::new(reader);
+
+            let mut manifest: Option<ArchiveManifest> = None;
+            let mut pending = Vec::new();
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                let path = entry.path()?.to_path_buf();
+                let name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .context("archive member has no name")?
+                    .to_string();
+                let mut buf = Vec::new();
+                std::io::Read::read_to_end(&mut entry, &mut buf)?;
+                if name == ARCHIVE_MANIFEST_NAME {
+                    manifest = Some(serde_json::from_slice(&buf)?);
+                } else {
+                    pending.push((name, buf));
+                }
+            }
+
+            let manifest = manifest.context("archive is missing its manifest")?;
+
+            for (name, bytes) in pending {
+                let meta = parse_snapshot_meta(&name)
+                    .with_context(|| format!("archive member `{name}` is not a snapshot"))?;
+                let member = manifest
+                    .members
+                    .iter()
+                    .find(|m| m.name == name)
+                    .with_context(|| format!("member `{name}` absent from manifest"))?;
+                if (member.start_frame_no, member.end_frame_no)
+                    != (meta.start_frame_no, meta.end_frame_no)
+                {
+                    bail!("manifest range for `{name}` does not match its file name");
+                }
+                if existing.iter().any(|(s, e)| {
+                    meta.start_frame_no <= *e && *s <= meta.end_frame_no
+                }) {
+                    bail!("member `{name}` overlaps an existing snapshot");
+                }
+                // Validate the content hash the manifest recorded against the bytes we received, so
+                // a tampered or truncated member is rejected rather than written into the served set.
+                if member.content_hash != snapshot_content_hash_bytes(&bytes) {
+                    bail!("member `{name}` content hash does not match the manifest");
+                }
+
+                // Write to a `.tmp` sibling and rename into place, matching the write-to-temp
+                // discipline the compactor uses so a file under its final name is always complete.
+                let dst = snapshot_dir_path.join(&name);
+                let tmp = snapshot_dir_path.join(format!("{name}{TMP_SNAPSHOT_SUFFIX}"));
+                std::fs::write(&tmp, &bytes)?;
+                std::fs::rename(&tmp, &dst)?;
+                existing.push((meta.start_frame_no, meta.end_frame_no));
+            }
+
+            Ok(())
+        })
+        .await??;
+
+        Ok(())
+    }
+}
+
 struct SnapshotMerger {
     /// Sending part of a channel of (snapshot_name, snapshot_frame_count, db_page_count) to the merger thread
     sender: mpsc::Sender<(String, u64, u32)>,
@@ -275,7 +742,7 @@ impl SnapshotMerger {
         db_path: &Path,
         log_id: Uuid,
     ) -> anyhow::Result<()> {
-        let mut snapshots = Self::init_snapshot_info_list(db_path).await?;
+        let mut snapshots = Recovery::run(db_path, log_id).await?;
         let mut working = false;
         let mut job: Pin<Box<dyn Future<Output = anyhow::Result<_>> + Sync + Send>> =
             Box::pin(std::future::pending());
@@ -283,7 +750,7 @@ impl SnapshotMerger {
             tokio::select! {
                 Some((name, size, db_page_count)) = receiver.recv() => {
                     snapshots.push((name, size));
-                    if !working && dbg!(Self::should_compact(&snapshots, db_page_count)) {
+                    if !working && Self::should_compact(&snapshots, db_page_count) {
                         let snapshots = std::mem::take(&mut snapshots);
                         let fut = async move {
                             let compacted_snapshot_info =
@@ -297,7 +764,7 @@ impl SnapshotMerger {
                 ret = &mut job, if working => {
                     working = false;
                     job = Box::pin(std::future::pending());
-                    let ret = dbg!(ret)?;
+                    let ret = ret?;
                     // the new merged snapshot is prepended to the snapshot list
                     snapshots.insert(0, ret);
                 }
@@ -307,11 +774,8 @@ impl SnapshotMerger {
     }
 
     /// Reads the snapshot dir and returns the list of snapshots along with their size, sorted in
-    /// chronological order.
-    ///
-    /// TODO: if the process was kill in the midst of merging snapshot, then the compacted snapshot
-    /// can exist alongside the snapshots it's supposed to have compacted. This is the place to
-    /// perform the cleanup.
+    /// chronological order. Corrupt snapshots are quarantined and skipped. De-overlapping of
+    /// snapshots left behind by an interrupted merge is handled by [`Recovery`].
     async fn init_snapshot_info_list(db_path: &Path) -> anyhow::Result<Vec<(String, u64)>> {
         let snapshot_dir_path = snapshot_dir_path(db_path);
         if !snapshot_dir_path.exists() {
@@ -324,6 +788,13 @@ impl SnapshotMerger {
         tokio::pin!(snapshots);
         while let Some(snapshot_name) = snapshots.next().await.transpose()? {
             let snapshot_path = snapshot_dir_path.join(&snapshot_name);
+            // Quarantine and skip snapshots that fail integrity verification so corruption never
+            // seeds the merger's working set.
+            if let Err(e) = verify_snapshot_integrity(&snapshot_path).await {
+                tracing::error!("{e}");
+                quarantine_snapshot(&snapshot_path).await?;
+                continue;
+            }
             let snapshot = SnapshotFile::open(&snapshot_path).await?;
             temp.push((
                 snapshot_name,
@@ -345,25 +816,56 @@ impl SnapshotMerger {
         db_path: &Path,
         log_id: Uuid,
     ) -> anyhow::Result<(String, u64)> {
-        let mut builder = SnapshotBuilder::new(dbg!(db_path), log_id).await?;
-        dbg!();
+        let mut builder = SnapshotBuilder::new(db_path, log_id).await?;
         let snapshot_dir_path = snapshot_dir_path(db_path);
+        tracing::debug!("merging {} snapshots for {log_id}", snapshots.len());
+
+        // Resolve the valid source snapshots, newest first. The size after the merged snapshot is
+        // the size after the newest source. Corrupt sources are quarantined and dropped, so only
+        // the names we actually keep drive the merged header range and the post-merge cleanup.
+        let mut sources = Vec::with_capacity(snapshots.len());
+        let mut kept = Vec::with_capacity(snapshots.len());
         let mut size_after = None;
-        tracing::debug!("merging {} snashots for {log_id}", snapshots.len());
         for (name, _) in snapshots.iter().rev() {
-            let snapshot = SnapshotFile::open(dbg!(&snapshot_dir_path.join(name))).await?;
-            dbg!();
-            // The size after the merged snapshot is the size after the first snapshot to be merged
+            let snapshot_path = snapshot_dir_path.join(name);
+            if let Err(e) = verify_snapshot_integrity(&snapshot_path).await {
+                tracing::error!("skipping corrupt snapshot during merge: {e}");
+                quarantine_snapshot(&snapshot_path).await?;
+                continue;
+            }
+            let snapshot = SnapshotFile::open(&snapshot_path).await?;
             if size_after.is_none() {
                 size_after.replace(snapshot.header().size_after);
             }
-            builder
-                .append_frames(snapshot.into_stream_mut().map_err(|e| anyhow::anyhow!(e)))
-                .await?;
+            sources.push(snapshot);
+            kept.push(name.clone());
         }
 
-        let (_, start_frame_no, _) = parse_snapshot_name(&snapshots[0].0).unwrap();
-        let (_, _, end_frame_no) = parse_snapshot_name(&snapshots.last().unwrap().0).unwrap();
+        if kept.is_empty() {
+            bail!("all snapshot sources failed verification; nothing to merge for {log_id}");
+        }
+
+        // Each `.snap` already stores its pages in descending frame_no order with no intra-file
+        // page duplicates. Decode the sources concurrently and feed a streaming k-way merge that
+        // always emits the highest remaining frame_no, so the output keeps the descending-order
+        // invariant `append_frames` relies on while the per-source decode runs in parallel.
+        let merged = kway_merge_descending(sources);
+        builder.append_frames(merged).await?;
+
+        // Derive the merged range from the kept sources only: a skipped-corrupt first or last
+        // source must not set a boundary the merged frames don't actually cover.
+        let start_frame_no = kept
+            .iter()
+            .filter_map(|n| parse_snapshot_name(n))
+            .map(|(_, start, _)| start)
+            .min()
+            .unwrap();
+        let end_frame_no = kept
+            .iter()
+            .filter_map(|n| parse_snapshot_name(n))
+            .map(|(_, _, end)| end)
+            .max()
+            .unwrap();
 
         tracing::debug!(
             "created merged snapshot for {log_id} from frame {start_frame_no} to {end_frame_no}"
@@ -375,7 +877,7 @@ impl SnapshotMerger {
 
         let meta = builder.finish().await?;
 
-        for (name, _) in snapshots.iter() {
+        for name in &kept {
             tokio::fs::remove_file(&snapshot_dir_path.join(name)).await?;
         }
 
@@ -405,13 +907,107 @@ impl SnapshotMerger {
     }
 }
 
+/// Startup reconciliation of the snapshot directory.
+///
+/// If the process was killed in the middle of `merge_snapshots`, the freshly written merged
+/// snapshot can coexist with the source snapshots it was meant to replace, leaving overlapping
+/// frame ranges that confuse `find_snapshot_file`. `Recovery` runs once before the merger loop
+/// starts: it deletes leftover `.tmp` partials, then detects when one snapshot's range fully
+/// covers a contiguous tiling of others with the same `log_id` and deletes those now-redundant
+/// covered snapshots, returning the reconciled, de-overlapped list that seeds the loop.
+struct Recovery;
+
+impl Recovery {
+    async fn run(db_path: &Path, log_id: Uuid) -> anyhow::Result<Vec<(String, u64)>> {
+        let snapshot_dir_path = snapshot_dir_path(db_path);
+        if !snapshot_dir_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        // Delete any leftover `.tmp` partials from an interrupted write.
+        remove_orphaned_tmp(&snapshot_dir_path).await?;
+
+        // Verified, chronologically sorted list. Corrupt snapshots have already been quarantined.
+        let snapshots = SnapshotMerger::init_snapshot_info_list(db_path).await?;
+        let redundant = Self::covered_snapshots(&snapshots, log_id);
+        if redundant.is_empty() {
+            return Ok(snapshots);
+        }
+
+        for name in &redundant {
+            tracing::info!("recovery: deleting snapshot `{name}` covered by a merged snapshot");
+            tokio::fs::remove_file(snapshot_dir_path.join(name)).await?;
+        }
+
+        Ok(snapshots
+            .into_iter()
+            .filter(|(name, _)| !redundant.contains(name))
+            .collect())
+    }
+
+    /// Return the names of snapshots whose `[start, end]` range is fully covered by a contiguous
+    /// tiling of a single larger snapshot with the same `log_id`. Those are the source snapshots
+    /// that a completed-but-uncommitted merge left behind.
+    fn covered_snapshots(snapshots: &[(String, u64)], log_id: Uuid) -> HashSet<String> {
+        let metas: Vec<_> = snapshots
+            .iter()
+            .filter_map(|(name, _)| parse_snapshot_meta(name).map(|m| (name.clone(), m)))
+            .filter(|(_, m)| m.db_id.as_u128() == log_id.as_u128())
+            .collect();
+
+        let mut covered = HashSet::new();
+        for (cover_name, cover) in &metas {
+            // Candidate members strictly inside the covering range.
+            let mut members: Vec<_> = metas
+                .iter()
+                .filter(|(name, m)| {
+                    name != cover_name
+                        && m.start_frame_no >= cover.start_frame_no
+                        && m.end_frame_no <= cover.end_frame_no
+                        && (m.start_frame_no, m.end_frame_no)
+                            != (cover.start_frame_no, cover.end_frame_no)
+                })
+                .cloned()
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            members.sort_by_key(|(_, m)| m.start_frame_no);
+
+            // The members must tile `[cover.start, cover.end]` with no gaps or overlaps.
+            let mut next = cover.start_frame_no;
+            let mut tiled = true;
+            for (_, m) in &members {
+                if m.start_frame_no != next {
+                    tiled = false;
+                    break;
+                }
+                next = m.end_frame_no + 1;
+            }
+            if tiled && next == cover.end_frame_no + 1 {
+                covered.extend(members.into_iter().map(|(name, _)| name));
+            }
+        }
+
+        covered
+    }
+}
+
 /// An utility to build a snapshots from log frames
 struct SnapshotBuilder {
     seen_pages: HashSet<u32>,
     header: SnapshotFileHeader,
-    snapshot_file: tokio::io::BufWriter<async_tempfile::TempFile>,
+    snapshot_file: tokio::io::BufWriter<tokio::fs::File>,
+    /// Path of the in-progress `.tmp` file, renamed into place by `finish`.
+    tmp_path: PathBuf,
     db_path: PathBuf,
     last_seen_frame_no: u64,
+    hasher: blake3::Hasher,
+    /// `(page_no, byte offset of its frame record)` pairs, built as frames are emitted and written
+    /// as a sorted footer so point reads can binary-search instead of scanning.
+    index: Vec<(u32, u64)>,
+    /// Byte offset of the next frame record, tracked so the index records exact positions.
+    offset: u64,
 }
 
 fn snapshot_dir_path(db_path: &Path) -> PathBuf {
@@ -422,7 +1018,11 @@ impl SnapshotBuilder {
     async fn new(db_path: &Path, log_id: Uuid) -> anyhow::Result<Self> {
         let snapshot_dir_path = snapshot_dir_path(db_path);
         std::fs::create_dir_all(&snapshot_dir_path)?;
-        let mut f = tokio::io::BufWriter::new(async_tempfile::TempFile::new().await?);
+        // Write to a `.tmp` sibling in the snapshot dir and rename into place in `finish`; a
+        // same-directory rename is atomic, so a file under its final name is always complete and
+        // any leftover `.tmp` is an identifiable partial that recovery can delete.
+        let tmp_path = snapshot_dir_path.join(format!("{}.tmp", Uuid::new_v4()));
+        let mut f = tokio::io::BufWriter::new(tokio::fs::File::create(&tmp_path).await?);
         // reserve header space
         f.write_all(&[0; size_of::<SnapshotFileHeader>()]).await?;
 
@@ -437,8 +1037,12 @@ impl SnapshotBuilder {
                 _pad: 0,
             },
             snapshot_file: f,
+            tmp_path,
             db_path: db_path.to_path_buf(),
             last_seen_frame_no: u64::MAX,
+            hasher: blake3::Hasher::new(),
+            index: Vec::new(),
+            offset: size_of::<SnapshotFileHeader>() as u64,
         })
     }
 
@@ -462,7 +1066,7 @@ impl SnapshotBuilder {
                 self.header.start_frame_no = frame.header().frame_no;
             }
 
-            if dbg!(frame.header().frame_no) >= dbg!(self.header.end_frame_no) {
+            if frame.header().frame_no >= self.header.end_frame_no {
                 self.header.end_frame_no = frame.header().frame_no;
                 self.header.size_after = frame.header().size_after;
             }
@@ -474,8 +1078,16 @@ impl SnapshotBuilder {
 
             if !self.seen_pages.contains(&frame.header().page_no) {
                 self.seen_pages.insert(frame.header().page_no);
+                // record the offset of this frame record before writing it.
+                self.index.push((frame.header().page_no, self.offset));
+                // Frames are written as-is: `SnapshotFile::into_stream_mut[_from]` in the external
+                // `libsql_replication` crate reads fixed `LogFile::FRAME_SIZE` records and has no
+                // decode hook, so any on-disk frame encoding (e.g. compression) would have to land
+                // there first. Until it does, the writer must match that reader byte-for-byte.
                 let data = frame.as_slice();
+                self.hasher.update(data);
                 self.snapshot_file.write_all(data).await?;
+                self.offset += data.len() as u64;
                 self.header.frame_count += 1;
             }
         }
@@ -487,19 +1099,42 @@ impl SnapshotBuilder {
     async fn finish(mut self) -> anyhow::Result<(String, u64, u32)> {
         self.snapshot_file.flush().await?;
         let mut file = self.snapshot_file.into_inner();
+
+        // Append the sorted page-offset index after the frame region, followed by its length, so a
+        // point read can binary-search it and seek straight to the frame. The index is hashed
+        // alongside the frames for integrity.
+        self.index.sort_unstable_by_key(|(page_no, _)| *page_no);
+        let mut index_bytes = Vec::with_capacity(self.index.len() * PAGE_INDEX_ENTRY_LEN);
+        for (page_no, offset) in &self.index {
+            index_bytes.extend_from_slice(&page_no.to_le_bytes());
+            index_bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        let index_len = index_bytes.len() as u64;
+        self.hasher.update(&index_bytes);
+        self.hasher.update(&index_len.to_le_bytes());
+        file.write_all(&index_bytes).await?;
+        file.write_all(&index_len.to_le_bytes()).await?;
+        self.header._pad |= PAGE_INDEX_FLAG;
+
+        // Append the content hash as a footer and flag it in the header so the reader knows to trim
+        // and verify it. The digest was computed incrementally as frames (and the index) were
+        // written, so this needs no second pass over the data.
+        let digest = self.hasher.finalize();
+        file.write_all(digest.as_bytes()).await?;
+        self.header._pad |= CONTENT_HASH_FLAG;
         file.seek(SeekFrom::Start(0)).await?;
         file.write_all(bytes_of(&self.header)).await?;
-        let snapshot_name = format!(
-            "{}-{}-{}.snap",
-            Uuid::from_u128(self.header.log_id),
-            self.header.start_frame_no,
-            self.header.end_frame_no,
-        );
+        let snapshot_name = SnapshotName {
+            db_id: Uuid::from_u128(self.header.log_id),
+            start_frame_no: self.header.start_frame_no,
+            end_frame_no: self.header.end_frame_no,
+        }
+        .to_file_name();
 
         file.sync_all().await?;
 
         tokio::fs::rename(
-            file.file_path(),
+            &self.tmp_path,
             snapshot_dir_path(&self.db_path).join(&snapshot_name),
         )
         .await?;
@@ -512,6 +1147,67 @@ impl SnapshotBuilder {
     }
 }
 
+/// Decode the given snapshots concurrently and merge their frames into a single stream ordered by
+/// descending frame_no.
+///
+/// Each source is already sorted descending with unique pages, so a k-way merge over the head of
+/// every source — always emitting the highest remaining frame_no — yields a globally descending
+/// stream. Because the inputs cover disjoint frame ranges, the same page may appear in several
+/// sources; emitting newest-first lets the downstream `append_frames` keep the most recent version
+/// via its `seen_pages` set. One bounded reader task per source keeps memory usage flat.
+fn kway_merge_descending(
+    sources: Vec<SnapshotFile>,
+) -> impl Stream<Item = anyhow::Result<FrameMut>> {
+    async_stream::try_stream! {
+        let mut receivers = Vec::with_capacity(sources.len());
+        for source in sources {
+            let (tx, rx) = mpsc::channel::<anyhow::Result<FrameMut>>(1);
+            tokio::spawn(async move {
+                let stream = source.into_stream_mut();
+                tokio::pin!(stream);
+                while let Some(frame) = stream.next().await {
+                    if tx
+                        .send(frame.map_err(|e| anyhow::anyhow!(e)))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+            receivers.push(rx);
+        }
+
+        // Peeked head frame of each source; `None` once a source is exhausted.
+        let mut heads: Vec<Option<FrameMut>> = Vec::with_capacity(receivers.len());
+        for rx in receivers.iter_mut() {
+            heads.push(match rx.recv().await {
+                Some(frame) => Some(frame?),
+                None => None,
+            });
+        }
+
+        loop {
+            // Select the source whose head has the highest frame_no.
+            let next = heads
+                .iter()
+                .enumerate()
+                .filter_map(|(i, h)| h.as_ref().map(|f| (i, f.header().frame_no)))
+                .max_by_key(|(_, frame_no)| *frame_no);
+            let Some((idx, _)) = next else {
+                break;
+            };
+
+            let frame = heads[idx].take().unwrap();
+            heads[idx] = match receivers[idx].recv().await {
+                Some(frame) => Some(frame?),
+                None => None,
+            };
+            yield frame;
+        }
+    }
+}
+
 async fn perform_compaction(
     db_path: &Path,
     file_to_compact: LogFile,
@@ -614,7 +1310,7 @@ mod test {
         tokio::task::spawn_blocking(move || {
             let (logfile, logfile_path) = make_logfile();
             compactor_clone
-                .compact(logfile, dbg!(logfile_path))
+                .compact(logfile, logfile_path)
                 .unwrap();
         })
         .await
@@ -731,7 +1427,7 @@ mod test {
             for _ in 0..10 {
                 let (logfile, logfile_path) = make_logfile();
                 compactor_clone
-                    .compact(logfile, dbg!(logfile_path))
+                    .compact(logfile, logfile_path)
                     .unwrap();
             }
         })
@@ -816,7 +1512,11 @@ mod test {
 
         let mut seen_frames = HashSet::new();
         let mut seen_page_no = HashSet::new();
-        let data = &snapshot[std::mem::size_of::<SnapshotFileHeader>()..];
+        // The frame region is exactly `frame_count` frames right after the header; the page-offset
+        // index and content hash footers follow it.
+        let frames_start = std::mem::size_of::<SnapshotFileHeader>();
+        let frames_end = frames_start + header.frame_count as usize * LogFile::FRAME_SIZE;
+        let data = &snapshot[frames_start..frames_end];
         data.chunks(LogFile::FRAME_SIZE).for_each(|f| {
             let frame = Frame::try_from(f).unwrap();
             assert!(!seen_frames.contains(&frame.header().frame_no));
@@ -829,6 +1529,14 @@ mod test {
         assert_eq!(seen_frames.len(), 25);
         assert_eq!(seen_page_no.len(), 25);
 
+        // point reads via the page-offset index return the newest version of each page.
+        for page_no in 0..25u32 {
+            let frame = frame_for_page(&snapshot_path, page_no).await.unwrap().unwrap();
+            assert_eq!(frame.header().page_no, page_no);
+            assert_eq!(frame.header().frame_no, 25 + page_no as u64);
+        }
+        assert!(frame_for_page(&snapshot_path, 999).await.unwrap().is_none());
+
         let snapshot_file = SnapshotFile::open(&snapshot_path).await.unwrap();
 
         let frames = snapshot_file.into_stream_mut_from(0);
@@ -842,4 +1550,115 @@ mod test {
 
         assert_eq!(expected_frame_no, 24);
     }
+
+    /// Compact a single logfile into `db_path` and return the resulting snapshot's name.
+    async fn create_one_snapshot(db_path: &Path, log_id: Uuid) -> String {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let mut log_file = LogFile::new(temp.as_file().try_clone().unwrap(), 0, None).unwrap();
+        log_file.header.log_id = log_id.as_u128();
+        log_file.write_header().unwrap();
+        for i in 0..10 {
+            let data = std::iter::repeat(0).take(4096).collect::<Bytes>();
+            log_file
+                .push_page(&WalPage {
+                    page_no: i,
+                    size_after: i + 1,
+                    data,
+                })
+                .unwrap();
+        }
+        log_file.commit().unwrap();
+
+        let compactor = LogCompactor::new(db_path, log_id, Box::new(|_| Ok(()))).unwrap();
+        tokio::task::spawn_blocking({
+            let compactor = compactor.clone();
+            move || compactor.compact(log_file, temp.path().to_path_buf()).unwrap()
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let mut dir = tokio::fs::read_dir(snapshot_dir_path(db_path)).await.unwrap();
+        let entry = dir.next_entry().await.unwrap().unwrap();
+        entry.file_name().to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn archive_export_import_round_trip() {
+        let src = tempdir().unwrap();
+        let log_id = Uuid::new_v4();
+        let name = create_one_snapshot(src.path(), log_id).await;
+        let original = read(snapshot_dir_path(src.path()).join(&name)).unwrap();
+
+        let archive = src.path().join("backup.tar");
+        SnapshotArchive::export(src.path(), log_id, &archive, ArchiveCompression::None)
+            .await
+            .unwrap();
+
+        let dst = tempdir().unwrap();
+        SnapshotArchive::import(dst.path(), &archive).await.unwrap();
+
+        let restored = read(snapshot_dir_path(dst.path()).join(&name)).unwrap();
+        assert_eq!(original, restored);
+        // import leaves no `.tmp` files behind.
+        assert!(!snapshot_dir_path(dst.path()).join(format!("{name}.tmp")).exists());
+    }
+
+    #[tokio::test]
+    async fn archive_import_rejects_overlap() {
+        let src = tempdir().unwrap();
+        let log_id = Uuid::new_v4();
+        create_one_snapshot(src.path(), log_id).await;
+        let archive = src.path().join("backup.tar");
+        SnapshotArchive::export(src.path(), log_id, &archive, ArchiveCompression::None)
+            .await
+            .unwrap();
+
+        let dst = tempdir().unwrap();
+        SnapshotArchive::import(dst.path(), &archive).await.unwrap();
+        // re-importing the same range must be rejected rather than duplicated.
+        let err = SnapshotArchive::import(dst.path(), &archive).await.unwrap_err();
+        assert!(err.to_string().contains("overlaps"));
+    }
+
+    #[tokio::test]
+    async fn archive_import_rejects_manifest_filename_mismatch() {
+        let src = tempdir().unwrap();
+        let log_id = Uuid::new_v4();
+        let name = create_one_snapshot(src.path(), log_id).await;
+        let bytes = read(snapshot_dir_path(src.path()).join(&name)).unwrap();
+
+        // Hand-build an archive whose manifest advertises a range that disagrees with the member's
+        // file name.
+        let meta = parse_snapshot_meta(&name).unwrap();
+        let manifest = ArchiveManifest {
+            log_id,
+            members: vec![ArchiveMember {
+                name: name.clone(),
+                start_frame_no: meta.start_frame_no,
+                end_frame_no: meta.end_frame_no + 1,
+                size: bytes.len() as u64,
+                content_hash: snapshot_content_hash_bytes(&bytes),
+            }],
+        };
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest).unwrap();
+        let archive = src.path().join("bad.tar");
+        {
+            let mut builder = tar::Builder::new(std::fs::File::create(&archive).unwrap());
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest_bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, ARCHIVE_MANIFEST_NAME, manifest_bytes.as_slice())
+                .unwrap();
+            let mut f = std::fs::File::open(snapshot_dir_path(src.path()).join(&name)).unwrap();
+            builder.append_file(&name, &mut f).unwrap();
+            builder.into_inner().unwrap();
+        }
+
+        let dst = tempdir().unwrap();
+        let err = SnapshotArchive::import(dst.path(), &archive).await.unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+    }
 }